@@ -0,0 +1,207 @@
+// Built-in deps
+use std::str::FromStr;
+// External imports
+// Workspace imports
+use zksync_types::{Account, AccountId, Address};
+// Local imports
+use self::records::{EthAccountType, StorageAccountCreation, StorageAccountSnapshot};
+use crate::{QueryResult, StorageError, StorageProcessor};
+
+pub mod records;
+
+/// The stored state of an account at both the last committed and the last verified block.
+#[derive(Debug, Clone, Default)]
+pub struct AccountState {
+    pub committed: Option<(AccountId, Account)>,
+    pub verified: Option<(AccountId, Account)>,
+}
+
+/// Account schema contains interfaces to interact with the accounts state, backed by
+/// the `account_creates` / `account_state_snapshots` / `eth_account_types` tables.
+#[derive(Debug)]
+pub struct AccountSchema<'a, 'c>(pub &'a mut StorageProcessor<'c>);
+
+impl<'a, 'c> AccountSchema<'a, 'c> {
+    /// Loads the both committed and verified state for the account by its address.
+    pub async fn account_state_by_address(&mut self, address: Address) -> QueryResult<AccountState> {
+        let account_id = match self.account_id_by_address(address).await? {
+            Some(id) => id,
+            None => return Ok(AccountState::default()),
+        };
+
+        self.account_state_by_id(account_id).await
+    }
+
+    /// Loads the both committed and verified state for the account by its id.
+    pub async fn account_state_by_id(&mut self, account_id: AccountId) -> QueryResult<AccountState> {
+        let committed = self
+            .last_committed_state_for_account(account_id)
+            .await?
+            .map(|account| (account_id, account));
+        let verified = self
+            .last_verified_state_for_account(account_id)
+            .await?
+            .map(|account| (account_id, account));
+
+        Ok(AccountState { committed, verified })
+    }
+
+    /// Loads the last committed state of the account, regardless of its verification status.
+    ///
+    /// Every block commit appends a snapshot row to `account_state_snapshots`; this simply
+    /// takes the most recent one for the account, verified or not.
+    pub async fn last_committed_state_for_account(
+        &mut self,
+        account_id: AccountId,
+    ) -> QueryResult<Option<Account>> {
+        let snapshot = sqlx::query_as!(
+            StorageAccountSnapshot,
+            r#"
+            SELECT account_id, block_number, is_verified, data
+            FROM account_state_snapshots
+            WHERE account_id = $1
+            ORDER BY block_number DESC
+            LIMIT 1
+            "#,
+            i64::from(account_id)
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        snapshot.map(|snapshot| snapshot.into_account()).transpose()
+    }
+
+    /// Loads the last verified (proven and executed on L1) state of the account.
+    ///
+    /// Unlike [`Self::last_committed_state_for_account`], this only considers snapshots
+    /// that have since been marked verified.
+    pub async fn last_verified_state_for_account(
+        &mut self,
+        account_id: AccountId,
+    ) -> QueryResult<Option<Account>> {
+        let snapshot = sqlx::query_as!(
+            StorageAccountSnapshot,
+            r#"
+            SELECT account_id, block_number, is_verified, data
+            FROM account_state_snapshots
+            WHERE account_id = $1 AND is_verified = true
+            ORDER BY block_number DESC
+            LIMIT 1
+            "#,
+            i64::from(account_id)
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        snapshot.map(|snapshot| snapshot.into_account()).transpose()
+    }
+
+    /// Resolves an account's address by its id.
+    pub async fn account_address_by_id(&mut self, account_id: AccountId) -> QueryResult<Option<Address>> {
+        let record = sqlx::query_as!(
+            StorageAccountCreation,
+            "SELECT account_id, address FROM account_creates WHERE account_id = $1",
+            i64::from(account_id)
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        Ok(record.map(|record| Address::from_slice(&record.address)))
+    }
+
+    /// Resolves an account's id by its address.
+    pub async fn account_id_by_address(&mut self, address: Address) -> QueryResult<Option<AccountId>> {
+        let record = sqlx::query_as!(
+            StorageAccountCreation,
+            "SELECT account_id, address FROM account_creates WHERE address = $1",
+            address.as_bytes()
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        Ok(record.map(|record| record.account_id as AccountId))
+    }
+
+    /// Loads the stored `EthAccountType` for the given account, if one was recorded.
+    pub async fn account_type_by_id(&mut self, account_id: AccountId) -> QueryResult<Option<EthAccountType>> {
+        let record = sqlx::query!(
+            "SELECT account_type FROM eth_account_types WHERE account_id = $1",
+            i64::from(account_id)
+        )
+        .fetch_optional(self.0.conn())
+        .await?;
+
+        record
+            .map(|record| EthAccountType::from_str(&record.account_type))
+            .transpose()
+    }
+
+    /// Stores the `EthAccountType` for the given account.
+    pub async fn set_account_type(
+        &mut self,
+        account_id: AccountId,
+        account_type: EthAccountType,
+    ) -> QueryResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO eth_account_types (account_id, account_type)
+            VALUES ($1, $2)
+            ON CONFLICT (account_id) DO UPDATE SET account_type = $2
+            "#,
+            i64::from(account_id),
+            account_type.to_string()
+        )
+        .execute(self.0.conn())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Cross-checks the indices backing the account getters above and reports storage
+    /// corruption explicitly instead of letting it surface as an innocuous `Ok(None)`.
+    ///
+    /// Three invariants are checked:
+    /// - `account_address_by_id` and `account_id_by_address` must round-trip for the account.
+    /// - If a verified state exists for the account, a committed state must exist too (an
+    ///   account cannot be verified without first having been committed).
+    /// - If the account is referenced by any committed block, it must have a stored
+    ///   `EthAccountType` (the type is assigned at account creation time and should never
+    ///   be missing for an account that has since been committed).
+    ///
+    /// Returns [`StorageError::Corruption`] on the first violated invariant, rather than
+    /// `Ok(None)`, so operators get an explicit signal instead of silently stale data.
+    pub async fn verify_account_consistency(&mut self, account_id: AccountId) -> QueryResult<()> {
+        let address = self
+            .account_address_by_id(account_id)
+            .await?
+            .ok_or_else(|| StorageError::Corruption(format!("no address stored for account {}", account_id)))?;
+
+        let round_tripped_id = self.account_id_by_address(address).await?;
+        if round_tripped_id != Some(account_id) {
+            return Err(StorageError::Corruption(format!(
+                "account {} resolves to address {:#x}, but that address resolves back to {:?}",
+                account_id, address, round_tripped_id
+            ))
+            .into());
+        }
+
+        let state = self.account_state_by_id(account_id).await?;
+        if state.verified.is_some() && state.committed.is_none() {
+            return Err(StorageError::Corruption(format!(
+                "account {} has a verified state but no committed state",
+                account_id
+            ))
+            .into());
+        }
+
+        if state.committed.is_some() && self.account_type_by_id(account_id).await?.is_none() {
+            return Err(StorageError::Corruption(format!(
+                "account {} is committed but has no stored EthAccountType",
+                account_id
+            ))
+            .into());
+        }
+
+        Ok(())
+    }
+}
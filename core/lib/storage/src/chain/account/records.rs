@@ -0,0 +1,72 @@
+// Built-in deps
+use std::{fmt, str::FromStr};
+// External imports
+// Workspace imports
+use zksync_types::Account;
+// Local imports
+use crate::{QueryResult, StorageError};
+
+/// Type of the L1 account backing an L2 account, as reported by the Ethereum account
+/// loader (e.g. a CREATE2-deployed smart contract wallet needs a different signature
+/// verification path than a plain EOA).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EthAccountType {
+    Owned,
+    CREATE2,
+}
+
+impl fmt::Display for EthAccountType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw = match self {
+            Self::Owned => "Owned",
+            Self::CREATE2 => "CREATE2",
+        };
+        write!(f, "{}", raw)
+    }
+}
+
+impl FromStr for EthAccountType {
+    type Err = StorageError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "Owned" => Ok(Self::Owned),
+            "CREATE2" => Ok(Self::CREATE2),
+            other => Err(StorageError::Corruption(format!(
+                "unknown eth_account_types.account_type value: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Raw row of the `account_creates` table, mapping an account id to the address it was
+/// created with.
+#[derive(Debug)]
+pub struct StorageAccountCreation {
+    pub account_id: i64,
+    pub address: Vec<u8>,
+}
+
+/// Raw row of the `account_state_snapshots` table: a serialized [`Account`] as of a
+/// given block, plus whether that block has since been verified.
+#[derive(Debug)]
+pub struct StorageAccountSnapshot {
+    pub account_id: i64,
+    pub block_number: i64,
+    pub is_verified: bool,
+    pub data: Vec<u8>,
+}
+
+impl StorageAccountSnapshot {
+    pub fn into_account(self) -> QueryResult<Account> {
+        bincode::deserialize(&self.data).map_err(|err| {
+            StorageError::Corruption(format!(
+                "failed to deserialize account {} snapshot at block {}: {}",
+                self.account_id, self.block_number, err
+            ))
+            .into()
+        })
+    }
+}
+
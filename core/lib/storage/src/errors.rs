@@ -0,0 +1,10 @@
+// Built-in deps
+// External imports
+use thiserror::Error;
+
+/// Errors that can occur while talking to the storage layer.
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("storage corruption detected: {0}")]
+    Corruption(String),
+}
@@ -11,7 +11,7 @@ use crate::{
         state::StateSchema,
     },
     test_data::gen_operation,
-    QueryResult, StorageProcessor,
+    QueryResult, StorageError, StorageProcessor,
 };
 
 /// The save/load routine for EthAccountType
@@ -98,8 +98,17 @@ async fn stored_accounts(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
                 .await?,
             Some(*account_id)
         );
+
+        // The address/id indices and the account type should be consistent for a
+        // healthy account.
+        AccountSchema(&mut storage)
+            .verify_account_consistency(*account_id)
+            .await?;
     }
 
+    // Pick an account to break later, before `accounts_block` is consumed below.
+    let corrupted_account_id = *accounts_block.keys().next().unwrap();
+
     // Now add a proof, verify block and apply a state update.
     OperationsSchema(&mut storage)
         .store_aggregated_action(gen_unique_aggregated_operation(
@@ -140,5 +149,20 @@ async fn stored_accounts(mut storage: StorageProcessor<'_>) -> QueryResult<()> {
         );
     }
 
+    // Artificially break the `eth_account_types` index row for a committed account:
+    // the verifier should now report corruption instead of pretending the account is fine.
+    sqlx::query("DELETE FROM eth_account_types WHERE account_id = $1")
+        .bind(corrupted_account_id as i64)
+        .execute(storage.conn())
+        .await?;
+
+    let verification_result = AccountSchema(&mut storage)
+        .verify_account_consistency(corrupted_account_id)
+        .await;
+    assert!(
+        matches!(verification_result, Err(StorageError::Corruption(_))),
+        "verifier did not detect the broken eth_account_types row"
+    );
+
     Ok(())
 }
@@ -1,15 +1,22 @@
-use std::iter;
+use std::path::Path;
 
-use num::BigUint;
-use rand::{seq::SliceRandom, Rng};
+use num::{BigUint, Zero};
+use rand::{Rng, RngCore};
+use serde::Deserialize;
+use thiserror::Error;
 
 use zksync_types::Address;
 
 use crate::{account_pool::AddressPool, rng::LoadtestRng};
 
+/// Smallest remaining budget for which a greedily drained batch keeps drawing random
+/// commands; once less than this is left, the whole residual goes to the batch's
+/// single final command.
+const MIN_PACKABLE_AMOUNT: u64 = 100;
+
 /// Type of transaction. It doesn't copy the zkSync operation list, because
 /// it divides some transactions in subcategories (e.g. to new account / to existing account; to self / to other; etc)/
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum TxType {
     Deposit,
     TransferToNew,
@@ -21,48 +28,31 @@ pub enum TxType {
 }
 
 impl TxType {
-    /// Generates a random transaction type. Not all the variants have the equal chance to be generated;
-    /// specifically transfers are made more likely.
-    pub fn random(rng: &mut LoadtestRng) -> Self {
-        // All available options.
-        let mut options = vec![
-            Self::Deposit,
-            Self::TransferToNew,
-            Self::TransferToExisting,
-            Self::WithdrawToSelf,
-            Self::WithdrawToOther,
-            Self::FullExit,
-            Self::ChangePubKey,
-        ];
-
-        // Make `TransferToNew` and `TransferToExisting` the most likely options
-        // by adding them multiple times.
-        let transfer_to_new_likehood = 0.3f64;
-        let transfer_to_existing_likehood = 0.4f64;
-
-        // We are ignoring the fact that variables in fact rely on each other; it's not that important for our purposes.
-        let required_transfer_to_new_copies =
-            Self::required_amount_of_copies(&options, transfer_to_new_likehood);
-        let required_transfer_to_existing_copies =
-            Self::required_amount_of_copies(&options, transfer_to_existing_likehood);
-        let total_new_elements =
-            required_transfer_to_new_copies + required_transfer_to_existing_copies;
-
-        options.reserve(total_new_elements);
-
-        options.extend(iter::repeat(Self::TransferToNew).take(required_transfer_to_new_copies));
-        options.extend(
-            iter::repeat(Self::TransferToExisting).take(required_transfer_to_existing_copies),
-        );
-
-        // Now we can get weighted element by simply choosing the random value from the vector.
-        options.choose(rng).copied().unwrap()
+    /// All the variants, in the order their weights are listed in [`TxDistribution`].
+    const ALL: [Self; 7] = [
+        Self::Deposit,
+        Self::TransferToNew,
+        Self::TransferToExisting,
+        Self::WithdrawToSelf,
+        Self::WithdrawToOther,
+        Self::FullExit,
+        Self::ChangePubKey,
+    ];
+
+    /// Generates a random transaction type, weighted according to `distribution`.
+    pub fn random(rng: &mut LoadtestRng, distribution: &TxDistribution) -> Self {
+        let choices: Vec<(Self, f64)> = Self::ALL
+            .iter()
+            .map(|&tx_type| (tx_type, distribution.tx_type_weight(tx_type)))
+            .collect();
+
+        *rng.weighted_choice(&choices)
     }
 
     /// Generates a random transaction type that can be a part of the batch.
-    pub fn random_batchable(rng: &mut LoadtestRng) -> Self {
+    pub fn random_batchable(rng: &mut LoadtestRng, distribution: &TxDistribution) -> Self {
         loop {
-            let output = Self::random(rng);
+            let output = Self::random(rng, distribution);
 
             // Priority ops and ChangePubKey cannot be inserted into the batch.
             if !matches!(output, Self::Deposit | Self::FullExit | Self::ChangePubKey) {
@@ -70,18 +60,12 @@ impl TxType {
             }
         }
     }
-
-    fn required_amount_of_copies(options: &[Self], likehood: f64) -> usize {
-        // This value will be truncated down, but it will be compensated by the fact
-        // that element is already inserted into `options`.
-        (options.len() as f64 * likehood) as usize
-    }
 }
 
 /// Modifier to be applied to the transaction in order to make it incorrect.
 /// Incorrect transactions are a significant part of loadtest, because we want to ensure
 /// that server is resilient for all the possible kinds of user input.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum IncorrectnessModifier {
     ZeroFee,
     IncorrectZkSyncSignature,
@@ -90,6 +74,7 @@ pub enum IncorrectnessModifier {
     TooBigAmount,
     NotPackableAmount,
     NotPackableFeeAmount,
+    InsufficientBalanceForFee,
 
     // Last option goes for no modifier,
     // since it's more convenient than dealing with `Option<IncorrectnessModifier>`.
@@ -99,7 +84,7 @@ pub enum IncorrectnessModifier {
 /// Expected outcome of transaction:
 /// Since we may create erroneous transactions on purpose,
 /// we may expect different outcomes for each transaction.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ExpectedOutcome {
     /// Transactions was successfully executed.
     TxSucceed,
@@ -111,29 +96,27 @@ pub enum ExpectedOutcome {
 }
 
 impl IncorrectnessModifier {
-    pub fn random(rng: &mut LoadtestRng) -> Self {
-        // 90% of transactions should be correct.
-        const NO_MODIFIER_PROBABILITY: f32 = 0.9f32;
-        // Amount of elements in the enum.
-        const MODIFIERS_AMOUNT: usize = 7;
-
-        let chance = rng.gen_range(0f32, 1f32);
-        if chance <= NO_MODIFIER_PROBABILITY {
-            return Self::None;
-        }
-
-        let modifier_type = rng.gen_range(0, MODIFIERS_AMOUNT);
-
-        match modifier_type {
-            0 => Self::ZeroFee,
-            1 => Self::IncorrectZkSyncSignature,
-            2 => Self::IncorrectEthSignature,
-            3 => Self::NonExistentToken,
-            4 => Self::TooBigAmount,
-            5 => Self::NotPackableAmount,
-            6 => Self::NotPackableFeeAmount,
-            _ => unreachable!("Unexpected modifier type number"),
-        }
+    /// All the variants, in the order their weights are listed in [`TxDistribution`].
+    const ALL: [Self; 9] = [
+        Self::None,
+        Self::ZeroFee,
+        Self::IncorrectZkSyncSignature,
+        Self::IncorrectEthSignature,
+        Self::NonExistentToken,
+        Self::TooBigAmount,
+        Self::NotPackableAmount,
+        Self::NotPackableFeeAmount,
+        Self::InsufficientBalanceForFee,
+    ];
+
+    /// Generates a random modifier, weighted according to `distribution`.
+    pub fn random(rng: &mut LoadtestRng, distribution: &TxDistribution) -> Self {
+        let choices: Vec<(Self, f64)> = Self::ALL
+            .iter()
+            .map(|&modifier| (modifier, distribution.modifier_weight(modifier)))
+            .collect();
+
+        *rng.weighted_choice(&choices)
     }
 
     pub fn expected_outcome(self) -> ExpectedOutcome {
@@ -148,10 +131,158 @@ impl IncorrectnessModifier {
             | Self::NotPackableFeeAmount => ExpectedOutcome::ApiRequestFailed,
 
             Self::TooBigAmount => ExpectedOutcome::TxRejected,
+
+            // The API only validates the signature against the declared fee; whether the
+            // sender can actually cover it is only known once the tx reaches execution.
+            Self::InsufficientBalanceForFee => ExpectedOutcome::TxRejected,
         }
     }
 }
 
+/// Configures how likely each [`TxType`] and [`IncorrectnessModifier`] is to be
+/// generated, so operators can shape a loadtest run (e.g. deposit-heavy, or an
+/// all-incorrect stress profile) without recompiling.
+///
+/// Weights don't need to sum to anything in particular: only their ratios matter, since
+/// [`LoadtestRng::weighted_choice`] normalizes by the total. [`TxDistribution::default`]
+/// reproduces the ratios the generator used before this type existed.
+#[derive(Debug, Clone)]
+pub struct TxDistribution {
+    tx_type_weights: [f64; 7],
+    modifier_weights: [f64; 9],
+}
+
+impl TxDistribution {
+    fn tx_type_weight(&self, tx_type: TxType) -> f64 {
+        let index = TxType::ALL.iter().position(|&t| t == tx_type).unwrap();
+        self.tx_type_weights[index]
+    }
+
+    fn modifier_weight(&self, modifier: IncorrectnessModifier) -> f64 {
+        let index = IncorrectnessModifier::ALL
+            .iter()
+            .position(|&m| m == modifier)
+            .unwrap();
+        self.modifier_weights[index]
+    }
+
+    /// Loads a distribution from a scenario file. The file is expected to contain a
+    /// [`TxDistributionConfig`]; fields left unset fall back to [`TxDistribution::default`].
+    pub fn from_scenario_file(path: impl AsRef<Path>) -> Result<Self, TxDistributionError> {
+        let contents = std::fs::read_to_string(path)?;
+        let config: TxDistributionConfig = serde_json::from_str(&contents)?;
+
+        Ok(config.into())
+    }
+}
+
+impl Default for TxDistribution {
+    fn default() -> Self {
+        TxDistributionConfig::default().into()
+    }
+}
+
+/// On-disk representation of a [`TxDistribution`], with one named weight per
+/// [`TxType`] and per [`IncorrectnessModifier`]. Missing fields default to the
+/// weights that reproduce the generator's original, hardcoded behavior.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TxDistributionConfig {
+    pub deposit: f64,
+    pub transfer_to_new: f64,
+    pub transfer_to_existing: f64,
+    pub withdraw_to_self: f64,
+    pub withdraw_to_other: f64,
+    pub full_exit: f64,
+    pub change_pub_key: f64,
+
+    pub no_modifier: f64,
+    pub zero_fee: f64,
+    pub incorrect_zksync_signature: f64,
+    pub incorrect_eth_signature: f64,
+    pub non_existent_token: f64,
+    pub too_big_amount: f64,
+    pub not_packable_amount: f64,
+    pub not_packable_fee_amount: f64,
+    pub insufficient_balance_for_fee: f64,
+}
+
+impl Default for TxDistributionConfig {
+    fn default() -> Self {
+        Self {
+            // Matches the ratios the old `TxType::random` produced: transfers were
+            // duplicated into a 7-entry option list ~0.3 and ~0.4 of its own length,
+            // which works out to both transfer kinds being 3x as likely as the rest.
+            deposit: 1.0,
+            transfer_to_new: 3.0,
+            transfer_to_existing: 3.0,
+            withdraw_to_self: 1.0,
+            withdraw_to_other: 1.0,
+            full_exit: 1.0,
+            change_pub_key: 1.0,
+
+            // Matches the old 90%-no-modifier split, evenly among the 8 modifiers.
+            no_modifier: 0.9,
+            zero_fee: 0.0125,
+            incorrect_zksync_signature: 0.0125,
+            incorrect_eth_signature: 0.0125,
+            non_existent_token: 0.0125,
+            too_big_amount: 0.0125,
+            not_packable_amount: 0.0125,
+            not_packable_fee_amount: 0.0125,
+            insufficient_balance_for_fee: 0.0125,
+        }
+    }
+}
+
+impl From<TxDistributionConfig> for TxDistribution {
+    fn from(config: TxDistributionConfig) -> Self {
+        Self {
+            tx_type_weights: [
+                config.deposit,
+                config.transfer_to_new,
+                config.transfer_to_existing,
+                config.withdraw_to_self,
+                config.withdraw_to_other,
+                config.full_exit,
+                config.change_pub_key,
+            ],
+            modifier_weights: [
+                config.no_modifier,
+                config.zero_fee,
+                config.incorrect_zksync_signature,
+                config.incorrect_eth_signature,
+                config.non_existent_token,
+                config.too_big_amount,
+                config.not_packable_amount,
+                config.not_packable_fee_amount,
+                config.insufficient_balance_for_fee,
+            ],
+        }
+    }
+}
+
+/// Error loading a [`TxDistribution`] from a scenario file.
+#[derive(Debug, Error)]
+pub enum TxDistributionError {
+    #[error("failed to read scenario file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse scenario file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Error returned when a batch cannot be composed to the requested specification.
+#[derive(Debug, Error)]
+pub enum BatchComposeError {
+    #[error(
+        "cannot compose a batch draining {target_spend}: wallet only has {wallet_balance}"
+    )]
+    InsufficientBalance {
+        target_spend: BigUint,
+        wallet_balance: BigUint,
+    },
+}
+
 /// Complete description of a transaction that must be executed by a test wallet.
 #[derive(Debug, Clone)]
 pub struct TxCommand {
@@ -163,47 +294,213 @@ pub struct TxCommand {
     pub to: Address,
     /// Transaction amount (0 if not applicable).
     pub amount: BigUint,
+    /// Fee to be paid for the transaction.
+    pub fee: BigUint,
 }
 
 impl TxCommand {
+    /// The outcome this command is expected to have once sent to the server, as
+    /// declared by its [`IncorrectnessModifier`].
+    pub fn expected_outcome(&self) -> ExpectedOutcome {
+        self.modifier.expected_outcome()
+    }
+
     pub fn change_pubkey(address: Address) -> Self {
         Self {
             command_type: TxType::ChangePubKey,
             modifier: IncorrectnessModifier::None,
             to: address,
             amount: 0u64.into(),
+            fee: 0u64.into(),
         }
     }
 
     /// Generates a fully random transaction command.
-    pub fn random(rng: &mut LoadtestRng, own_address: Address, addresses: &AddressPool) -> Self {
-        let command_type = TxType::random(rng);
-
-        Self::new_with_type(rng, own_address, addresses, command_type)
+    ///
+    /// `own_balance` is the sender's current spendable balance; it's only consulted
+    /// when the generator happens to draw [`IncorrectnessModifier::InsufficientBalanceForFee`].
+    pub fn random(
+        rng: &mut LoadtestRng,
+        own_address: Address,
+        own_balance: &BigUint,
+        addresses: &AddressPool,
+        distribution: &TxDistribution,
+    ) -> Self {
+        let command_type = TxType::random(rng, distribution);
+
+        Self::new_with_type(
+            rng,
+            own_address,
+            own_balance,
+            addresses,
+            distribution,
+            command_type,
+        )
     }
 
     /// Generates a random transaction command that can be a part of the batch.
+    ///
+    /// See [`Self::random`] for the meaning of `own_balance`.
     pub fn random_batchable(
         rng: &mut LoadtestRng,
         own_address: Address,
+        own_balance: &BigUint,
         addresses: &AddressPool,
+        distribution: &TxDistribution,
     ) -> Self {
-        let command_type = TxType::random_batchable(rng);
+        let command_type = TxType::random_batchable(rng, distribution);
+
+        Self::new_with_type(
+            rng,
+            own_address,
+            own_balance,
+            addresses,
+            distribution,
+            command_type,
+        )
+    }
+
+    /// Composes a batch of batchable transfer commands whose amounts and fees sum up
+    /// exactly to `target_spend`, instead of drawing each command's amount independently.
+    ///
+    /// Starting from a remaining budget equal to `target_spend`, every command but the
+    /// last is given a random amount bounded by what's left; the last command receives
+    /// whatever remains so the batch drains precisely the requested sum. Stops early
+    /// (before `max_batch_size` is reached) once the remaining budget drops below
+    /// [`MIN_PACKABLE_AMOUNT`].
+    ///
+    /// Returns an error if `target_spend` exceeds `wallet_balance`, since there would be
+    /// no way to compose a batch that the wallet can actually afford.
+    ///
+    /// `wallet_balance` and `target_spend` are kept as `BigUint` throughout, rather than
+    /// narrowed to a `u64`, since realistic (18-decimal) token balances don't fit one.
+    pub fn greedy_draining_batch(
+        rng: &mut LoadtestRng,
+        own_address: Address,
+        addresses: &AddressPool,
+        distribution: &TxDistribution,
+        wallet_balance: BigUint,
+        target_spend: BigUint,
+        max_batch_size: usize,
+    ) -> Result<Vec<Self>, BatchComposeError> {
+        if target_spend > wallet_balance {
+            return Err(BatchComposeError::InsufficientBalance {
+                target_spend,
+                wallet_balance,
+            });
+        }
+
+        let min_packable_amount = BigUint::from(MIN_PACKABLE_AMOUNT);
+        let mut remaining = target_spend;
+        let mut batch = Vec::new();
+
+        // Withdrawals are batchable too, but only transfers go into a draining batch.
+        let random_transfer_type = |rng: &mut LoadtestRng| loop {
+            let command_type = TxType::random_batchable(rng, distribution);
+            if matches!(command_type, TxType::TransferToNew | TxType::TransferToExisting) {
+                break command_type;
+            }
+        };
+
+        while !remaining.is_zero() && batch.len() + 1 < max_batch_size {
+            if remaining < min_packable_amount {
+                break;
+            }
+
+            let command_type = random_transfer_type(rng);
+            let mut command = Self::new_with_type(
+                rng,
+                own_address,
+                &wallet_balance,
+                addresses,
+                distribution,
+                command_type,
+            );
+
+            // The greedy strategy owns amount/fee assignment: per-command modifiers
+            // tampering with them would break the "batch sums to `target_spend`"
+            // invariant that this whole method exists to guarantee.
+            command.modifier = IncorrectnessModifier::None;
+
+            let fee = Self::random_fee(rng).min(remaining.clone());
+            let spendable = remaining.clone() - &fee;
+            let amount = Self::random_biguint_below(rng, &spendable);
+
+            remaining -= &amount + &fee;
 
-        Self::new_with_type(rng, own_address, addresses, command_type)
+            command.fee = fee;
+            command.amount = amount;
+            batch.push(command);
+        }
+
+        // The final command gets the residual, so the whole batch drains precisely
+        // `target_spend` instead of undershooting it. It still needs a non-zero fee:
+        // the repo's own `ZeroFee` modifier establishes that the API rejects a zero
+        // fee, so hardcoding one here would make the last leg of the batch fail and
+        // contradict the "whole batch succeeds" invariant this method exists for.
+        if !remaining.is_zero() {
+            let command_type = random_transfer_type(rng);
+            let mut command = Self::new_with_type(
+                rng,
+                own_address,
+                &wallet_balance,
+                addresses,
+                distribution,
+                command_type,
+            );
+            command.modifier = IncorrectnessModifier::None;
+
+            let fee = Self::random_fee(rng).min(remaining.clone());
+            command.amount = remaining - &fee;
+            command.fee = fee;
+            batch.push(command);
+        }
+
+        Ok(batch)
+    }
+
+    /// Draws a uniformly random `BigUint` in `[0, bound]` via rejection sampling: the
+    /// generator otherwise only ever needs `u64`-ranged amounts (see
+    /// [`Self::random_amount`]), but a greedily-drained batch has to fit within
+    /// whatever's left of an arbitrarily large `bound`.
+    fn random_biguint_below(rng: &mut LoadtestRng, bound: &BigUint) -> BigUint {
+        if bound.is_zero() {
+            return BigUint::zero();
+        }
+
+        let bits = bound.bits();
+        let bytes_needed = ((bits + 7) / 8) as usize;
+        let top_bit_mask = match bits % 8 {
+            0 => 0xffu8,
+            remainder => (1u8 << remainder) - 1,
+        };
+
+        loop {
+            let mut bytes = vec![0u8; bytes_needed];
+            rng.fill_bytes(&mut bytes);
+            bytes[0] &= top_bit_mask;
+
+            let candidate = BigUint::from_bytes_be(&bytes);
+            if candidate <= *bound {
+                return candidate;
+            }
+        }
     }
 
     fn new_with_type(
         rng: &mut LoadtestRng,
         own_address: Address,
+        own_balance: &BigUint,
         addresses: &AddressPool,
+        distribution: &TxDistribution,
         command_type: TxType,
     ) -> Self {
         let mut command = Self {
             command_type,
-            modifier: IncorrectnessModifier::random(rng),
+            modifier: IncorrectnessModifier::random(rng, distribution),
             to: addresses.random_address(rng),
             amount: Self::random_amount(rng),
+            fee: Self::random_fee(rng),
         };
 
         // Check whether we should use a non-existent address.
@@ -228,7 +525,9 @@ impl TxCommand {
                 command.modifier,
                 IncorrectnessModifier::TooBigAmount | IncorrectnessModifier::NotPackableAmount
             );
-        // It doesn't make sense to fail contract-based functions.
+        // It doesn't make sense to fail contract-based functions: priority ops are
+        // validated and paid for on L1, so neither a forged signature nor an
+        // under-funded L2 balance can make the server reject them at execution time.
         let incorrect_priority_op =
             matches!(command.command_type, TxType::Deposit | TxType::FullExit);
         // Amount doesn't have to be packable for withdrawals.
@@ -247,10 +546,22 @@ impl TxCommand {
             command.modifier = IncorrectnessModifier::None;
         }
 
+        // For this modifier the amount and fee have to be crafted so that their sum
+        // exceeds what the sender can actually spend: otherwise the server would have
+        // no reason to reject the transaction at execution time.
+        if command.modifier == IncorrectnessModifier::InsufficientBalanceForFee {
+            command.amount = own_balance.clone();
+            command.fee = own_balance + Self::random_amount(rng) + BigUint::from(1u64);
+        }
+
         command
     }
 
     fn random_amount(rng: &mut LoadtestRng) -> BigUint {
         rng.gen_range(0u64, 2u64.pow(18)).into()
     }
+
+    fn random_fee(rng: &mut LoadtestRng) -> BigUint {
+        rng.gen_range(1u64, 2u64.pow(10)).into()
+    }
 }
@@ -0,0 +1,49 @@
+// Built-in deps
+// External imports
+use num::BigUint;
+use zksync_types::Address;
+// Workspace imports
+// Local imports
+use crate::{
+    account_pool::AddressPool,
+    command::{ExpectedOutcome, TxCommand, TxDistribution},
+    report::ScanSummary,
+    rng::LoadtestRng,
+};
+
+/// Sends a single [`TxCommand`] to the server under test and reports what actually
+/// happened to it. The real implementation lives wherever this loadtest run's
+/// transport does (an RPC client talking to the server); [`run_scan`] only needs the
+/// realized outcome back, so it can be checked against what the generator expected.
+pub trait CommandTransport {
+    fn send(&mut self, command: &TxCommand) -> ExpectedOutcome;
+}
+
+/// Generates `iterations` random commands, sends each one through `transport`, and
+/// records the realized outcome against what [`TxCommand::expected_outcome`] declared
+/// for it, so that a regression in server-side validation shows up as a reconciliation
+/// failure instead of passing unnoticed.
+///
+/// Returns the process exit code the loadtest binary should terminate with (see
+/// [`ScanSummary::reconcile`]).
+pub fn run_scan(
+    rng: &mut LoadtestRng,
+    own_address: Address,
+    own_balance: &BigUint,
+    addresses: &AddressPool,
+    distribution: &TxDistribution,
+    transport: &mut impl CommandTransport,
+    iterations: usize,
+) -> i32 {
+    let mut summary = ScanSummary::new();
+
+    for _ in 0..iterations {
+        let command = TxCommand::random(rng, own_address, own_balance, addresses, distribution);
+        let expected = command.expected_outcome();
+        let observed = transport.send(&command);
+
+        summary.record(command.command_type, command.modifier, expected, observed);
+    }
+
+    summary.reconcile()
+}
@@ -0,0 +1,130 @@
+// Built-in deps
+use std::collections::HashMap;
+
+// Local imports
+use crate::command::{ExpectedOutcome, IncorrectnessModifier, TxType};
+
+/// A single command category: what kind of transaction it was, and how the generator
+/// tried to make it (mis)behave.
+type Category = (TxType, IncorrectnessModifier);
+
+/// Tally kept for a single [`Category`]: the outcome the generator declared for it,
+/// how many commands of that shape were generated, and a breakdown of what actually
+/// happened to them once executed.
+#[derive(Debug, Clone)]
+struct CategoryTally {
+    expected: ExpectedOutcome,
+    generated: u64,
+    observed: HashMap<ExpectedOutcome, u64>,
+}
+
+/// A category whose observed outcome didn't match what the generator declared via
+/// [`IncorrectnessModifier::expected_outcome`] — e.g. a `ZeroFee` command that
+/// unexpectedly succeeded, or a `TooBigAmount` that was accepted rather than rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub command_type: TxType,
+    pub modifier: IncorrectnessModifier,
+    pub expected: ExpectedOutcome,
+    pub observed: ExpectedOutcome,
+    pub count: u64,
+}
+
+/// Accumulates, per [`TxType`] and [`IncorrectnessModifier`], what the generator
+/// expected a command to do versus what the server actually did with it, so that a
+/// regression in server-side validation shows up as a reconciliation failure instead
+/// of passing unnoticed.
+///
+/// Today the generator encodes its expectations in [`ExpectedOutcome`], but nothing
+/// checks them in aggregate across a run; `ScanSummary` closes that gap.
+#[derive(Debug, Clone, Default)]
+pub struct ScanSummary {
+    tallies: HashMap<Category, CategoryTally>,
+}
+
+impl ScanSummary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the realized outcome for a single executed command. `expected` should
+    /// come from `modifier.expected_outcome()`; `observed` is whatever the test runner
+    /// determined actually happened (API rejection, execution rejection, or success).
+    pub fn record(
+        &mut self,
+        command_type: TxType,
+        modifier: IncorrectnessModifier,
+        expected: ExpectedOutcome,
+        observed: ExpectedOutcome,
+    ) {
+        let tally = self
+            .tallies
+            .entry((command_type, modifier))
+            .or_insert_with(|| CategoryTally {
+                expected,
+                generated: 0,
+                observed: HashMap::new(),
+            });
+        tally.generated += 1;
+        *tally.observed.entry(observed).or_insert(0) += 1;
+    }
+
+    /// Every category whose observed outcomes diverged, even partially, from what was
+    /// expected for it.
+    pub fn divergences(&self) -> Vec<Divergence> {
+        self.tallies
+            .iter()
+            .flat_map(|(&(command_type, modifier), tally)| {
+                let expected = tally.expected;
+                tally
+                    .observed
+                    .iter()
+                    .filter(move |&(&observed, _)| observed != expected)
+                    .map(move |(&observed, &count)| Divergence {
+                        command_type,
+                        modifier,
+                        expected,
+                        observed,
+                        count,
+                    })
+            })
+            .collect()
+    }
+
+    /// Total amount of commands recorded so far, across all categories.
+    pub fn total_generated(&self) -> u64 {
+        self.tallies.values().map(|tally| tally.generated).sum()
+    }
+
+    /// Logs a human-readable reconciliation report and returns the process exit code
+    /// the loadtest run should terminate with: `0` if every observed outcome matched
+    /// what was expected, `1` if any category diverged.
+    pub fn reconcile(&self) -> i32 {
+        let divergences = self.divergences();
+
+        if divergences.is_empty() {
+            vlog::info!(
+                "Reconciliation OK: all {} generated commands behaved as expected",
+                self.total_generated()
+            );
+            return 0;
+        }
+
+        vlog::error!(
+            "Reconciliation FAILED: {} categories diverged from their expected outcome",
+            divergences.len()
+        );
+        for divergence in &divergences {
+            vlog::error!(
+                "  {:?} / {:?}: expected {:?}, observed {:?} ({} occurrence(s))",
+                divergence.command_type,
+                divergence.modifier,
+                divergence.expected,
+                divergence.observed,
+                divergence.count
+            );
+        }
+
+        1
+    }
+}
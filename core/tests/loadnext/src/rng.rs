@@ -0,0 +1,69 @@
+// Built-in deps
+// External imports
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+
+/// Seedable RNG used throughout the loadtest command generator. Wrapping `StdRng`
+/// keeps the generator's randomness source swappable and gives a single place to add
+/// generator-specific helpers, such as weighted choice, without polluting every call
+/// site with extra `rand` trait imports.
+///
+/// Since it's seedable, the same seed (plus the same [`TxDistribution`](crate::command::TxDistribution))
+/// always produces an identical command stream, which is what makes loadtest runs
+/// reproducible.
+#[derive(Debug, Clone)]
+pub struct LoadtestRng(StdRng);
+
+impl LoadtestRng {
+    pub fn new(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+
+    /// Picks one of `choices` at random, with the probability of each entry
+    /// proportional to its weight, via a cumulative-weight binary search.
+    ///
+    /// Panics if `choices` is empty or the weights don't sum to a positive number.
+    pub fn weighted_choice<'a, T>(&mut self, choices: &'a [(T, f64)]) -> &'a T {
+        assert!(
+            !choices.is_empty(),
+            "weighted_choice requires at least one choice"
+        );
+
+        let mut cumulative_weights = Vec::with_capacity(choices.len());
+        let mut running_total = 0.0;
+        for (_, weight) in choices {
+            running_total += weight;
+            cumulative_weights.push(running_total);
+        }
+        assert!(
+            running_total > 0.0,
+            "weighted_choice requires a positive total weight"
+        );
+
+        let sample = self.gen_range(0.0, running_total);
+        let index = cumulative_weights
+            .binary_search_by(|cumulative_weight| {
+                cumulative_weight.partial_cmp(&sample).unwrap()
+            })
+            .unwrap_or_else(|insert_at| insert_at);
+
+        &choices[index.min(choices.len() - 1)].0
+    }
+}
+
+impl RngCore for LoadtestRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}